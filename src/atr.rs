@@ -0,0 +1,120 @@
+use crate::shashmap::Shashmap;
+use crate::slip::Slip;
+use crate::transaction::{Transaction, TransactionType};
+
+/// Automatic Transaction Rebroadcasting (ATR).
+///
+/// Saito keeps the UTXO set bounded by rebroadcasting unspent slips that
+/// have survived too long, rather than letting them sit forever. Scan
+/// `utxo` for slips that are still unspent but were confirmed before
+/// `current_block_id - epoch_length`, and emit an `ATR` transaction for
+/// each one that spends the stale slip and recreates an output owned by
+/// the same public key, minus `fee`, carrying the original slip's data
+/// in `message`.
+///
+/// Every stale slip is spent by its `ATR` transaction, so the UTXO set
+/// always shrinks monotonically for abandoned coins: if the slip's
+/// value wouldn't exceed `fee`, the rebroadcast transaction carries no
+/// output at all and the entire value is collected as fees, rather than
+/// the slip being left unspent and rescanned forever.
+pub fn rebroadcast_stale_slips(
+    utxo: &Shashmap,
+    current_block_id: u64,
+    epoch_length: u64,
+    fee: u64,
+) -> Vec<Transaction> {
+    let staleness_cutoff = current_block_id.saturating_sub(epoch_length);
+
+    utxo.unspent_slips()
+        .filter(|(_, confirmed_block_id)| *confirmed_block_id < staleness_cutoff)
+        .map(|(slip, _)| rebroadcast_slip(slip, fee))
+        .collect()
+}
+
+/// Build the `ATR` transaction that rebroadcasts a single stale `slip`,
+/// spending it unconditionally. If `fee` would consume its entire value
+/// the transaction carries no output, and the value is collected as fees.
+fn rebroadcast_slip(slip: &Slip, fee: u64) -> Transaction {
+    let amount = slip.get_amount();
+
+    let mut tx = Transaction::default();
+    tx.set_transaction_type(TransactionType::ATR);
+    tx.set_message(slip.serialize_for_signature());
+    tx.add_input(*slip);
+
+    if amount > fee {
+        let mut output = *slip;
+        output.set_amount(amount - fee);
+        tx.add_output(output);
+    }
+
+    tx
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        keypair::Keypair,
+        slip::SlipBroadcastType,
+    };
+
+    #[test]
+    fn rebroadcast_stale_slips_skips_fresh_slips_test() {
+        let mut utxo = Shashmap::new();
+        let keypair = Keypair::new();
+        let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 100);
+        utxo.insert(slip, 10, TransactionType::Normal);
+
+        let atr_txs = rebroadcast_stale_slips(&utxo, 15, 20, 1);
+        assert!(atr_txs.is_empty());
+    }
+
+    #[test]
+    fn rebroadcast_stale_slips_rebroadcasts_old_unspent_slips_test() {
+        let mut utxo = Shashmap::new();
+        let keypair = Keypair::new();
+        let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 100);
+        utxo.insert(slip, 10, TransactionType::Normal);
+
+        let atr_txs = rebroadcast_stale_slips(&utxo, 100, 20, 1);
+        assert_eq!(atr_txs.len(), 1);
+
+        let atr_tx = &atr_txs[0];
+        assert_eq!(atr_tx.get_transaction_type(), TransactionType::ATR);
+        assert_eq!(atr_tx.get_inputs(), &vec![slip]);
+        assert_eq!(atr_tx.get_outputs()[0].get_amount(), 99);
+    }
+
+    #[test]
+    fn rebroadcast_stale_slips_spends_slips_below_fee_with_no_output_test() {
+        let mut utxo = Shashmap::new();
+        let keypair = Keypair::new();
+        let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 1);
+        utxo.insert(slip, 10, TransactionType::Normal);
+
+        let atr_txs = rebroadcast_stale_slips(&utxo, 100, 20, 5);
+        assert_eq!(atr_txs.len(), 1);
+
+        let atr_tx = &atr_txs[0];
+        assert_eq!(atr_tx.get_transaction_type(), TransactionType::ATR);
+        assert_eq!(atr_tx.get_inputs(), &vec![slip]);
+        assert!(atr_tx.get_outputs().is_empty());
+    }
+
+    #[test]
+    fn rebroadcast_stale_slips_produces_a_transaction_that_validates_test() {
+        // rebroadcast_slip never signs the ATR transaction it builds, since
+        // it is generated on the original owner's behalf rather than by
+        // them. Transaction::validate must not demand an owner signature
+        // for ATR, or this entire feature could never pass validation.
+        let mut utxo = Shashmap::new();
+        let keypair = Keypair::new();
+        let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 100);
+        utxo.insert(slip, 10, TransactionType::Normal);
+
+        let atr_txs = rebroadcast_stale_slips(&utxo, 100, 20, 1);
+        assert_eq!(atr_txs.len(), 1);
+        assert!(atr_txs[0].validate(100));
+    }
+}
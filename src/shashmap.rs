@@ -1,12 +1,36 @@
 use crate::slip::Slip;
-use crate::transaction::Transaction;
+use crate::transaction::{Transaction, TransactionType};
 use std::collections::HashMap;
-/// A hashmap storing the byte arrays of `Slip`s as keys
-/// with the `Block` ids as values. This is used to enforce when
-/// `Slip`s have been spent in the network
+
+/// The spend status of a `Slip` in the UTXO set.
+///
+/// This replaces the old convention of overloading an `i64` (`-1` for
+/// unspent, any non-negative value for "spent in block N"), which made
+/// it impossible to tell a slip that has never existed apart from one
+/// that exists but hasn't been confirmed into a block yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlipSpentStatus {
+    /// The slip is confirmed in the UTXO set and has not been spent.
+    Unspent,
+    /// The slip has been consumed as a transaction input.
+    Spent,
+    /// The slip has been added (e.g. as the output of a newly received
+    /// `Transaction`) but the `Block` confirming it has not landed yet.
+    Unconfirmed,
+}
+
+/// A hashmap storing `Slip`s as keys, with their `SlipSpentStatus`, the
+/// `Block` id that status applies to (the block a spend happened in, or
+/// the block a slip was confirmed unspent in), and the `TransactionType`
+/// that produced the slip as values. This is used to enforce when
+/// `Slip`s have been spent in the network, to answer whether a slip is
+/// actually spendable, to find slips that have sat unspent long enough
+/// to need rebroadcasting (see `crate::atr`), and to let a
+/// `StakerWithdrawal` prove the slip it spends actually came from a
+/// prior `StakerDeposit` (see `Shashmap::slip_transaction_type`).
 #[derive(Debug, Clone)]
 pub struct Shashmap {
-    utxo_hashmap: HashMap<Slip, i64>,
+    utxo_hashmap: HashMap<Slip, (SlipSpentStatus, u64, TransactionType)>,
 }
 
 impl Shashmap {
@@ -17,39 +41,66 @@ impl Shashmap {
         }
     }
 
-    /// Insert serizialized slip into UTXO hashmap
+    /// The `TransactionType` a `slip` is already recorded under, or
+    /// `TransactionType::Normal` if it isn't present yet. Spend/unspend
+    /// operations use this to carry a slip's producing type forward
+    /// across status changes instead of losing it.
+    fn existing_type(&self, slip: &Slip) -> TransactionType {
+        self.utxo_hashmap
+            .get(slip)
+            .map(|(_, _, transaction_type)| *transaction_type)
+            .unwrap_or(TransactionType::Normal)
+    }
+
+    /// Insert a `Slip` into the UTXO hashmap as confirmed unspent as of
+    /// the given `Block` id, produced by a transaction of `transaction_type`
     ///
     /// * `slip` - `Slip` as our key
-    /// * `id` - `Block` id
-    pub fn insert(&mut self, slip: Slip, id: u64) {
-        self.utxo_hashmap.insert(slip, id as i64);
+    /// * `id` - `Block` id the slip was confirmed in
+    /// * `transaction_type` - `TransactionType` of the transaction that produced the slip
+    pub fn insert(&mut self, slip: Slip, id: u64, transaction_type: TransactionType) {
+        self.utxo_hashmap
+            .insert(slip, (SlipSpentStatus::Unspent, id, transaction_type));
     }
 
-    /// Insert serizialized slip into UTXO hashmap
+    /// Insert the outputs of a `Transaction` into the UTXO hashmap as
+    /// unconfirmed, since the transaction creating them has not yet
+    /// been confirmed into a `Block`
     ///
     /// * `tx` - `Transaction` which the outputs are inserted into `HashMap`
     pub fn insert_new_transaction(&mut self, tx: &Transaction) {
         for output in tx.outputs().iter() {
-            self.utxo_hashmap.insert(*output, -1);
+            self.utxo_hashmap.insert(
+                *output,
+                (SlipSpentStatus::Unconfirmed, 0, tx.get_transaction_type()),
+            );
         }
     }
 
-    /// Insert the inputs of a `Transaction` with the `Block` id
+    /// Mark the inputs of a `Transaction` as spent in the given `Block` id
     ///
     /// * `tx` - `Transaction` which the inputs are inserted into `HashMap`
     /// * `block_id` - `Block` id used as value
     pub fn spend_transaction(&mut self, tx: &Transaction, block_id: u64) {
         for input in tx.inputs().iter() {
-            self.utxo_hashmap.insert(*input, block_id as i64);
+            let transaction_type = self.existing_type(input);
+            self.utxo_hashmap
+                .insert(*input, (SlipSpentStatus::Spent, block_id, transaction_type));
         }
     }
 
-    /// Remove the inputs of a `Transaction` with the `Block` id
+    /// Revert the inputs of a `Transaction` back to unspent as of
+    /// `block_id`, and remove its outputs from the `HashMap`
     ///
-    /// * `tx` - `Transaction` where inputs are inserted, and outputs are removed
-    pub fn unspend_transaction(&mut self, tx: &Transaction) {
+    /// * `tx` - `Transaction` where inputs are reverted, and outputs are removed
+    /// * `block_id` - `Block` id the inputs are reverted to being unspent as of
+    pub fn unspend_transaction(&mut self, tx: &Transaction, block_id: u64) {
         for input in tx.inputs().iter() {
-            self.utxo_hashmap.insert(*input, -1);
+            let transaction_type = self.existing_type(input);
+            self.utxo_hashmap.insert(
+                *input,
+                (SlipSpentStatus::Unspent, block_id, transaction_type),
+            );
         }
 
         for outer in tx.outputs().iter() {
@@ -57,26 +108,80 @@ impl Shashmap {
         }
     }
 
-    /// Insert a `Slip`s byte array with the `Block` id
+    /// Mark a `Slip` as spent in the given `Block` id
     ///
     /// * `slip` - `Slip` as key
     /// * `block_id` - `Block` id as value
     pub fn spend_slip(&mut self, slip: &Slip, _bid: u64) {
-        self.utxo_hashmap.insert(*slip, _bid as i64);
+        let transaction_type = self.existing_type(slip);
+        self.utxo_hashmap
+            .insert(*slip, (SlipSpentStatus::Spent, _bid, transaction_type));
+    }
+
+    /// Revert a `Slip` back to unspent as of `block_id`
+    ///
+    /// * `slip` - `&Slip` as key
+    /// * `block_id` - `Block` id the slip is reverted to being unspent as of
+    pub fn unspend_slip(&mut self, slip: &Slip, block_id: u64) {
+        let transaction_type = self.existing_type(slip);
+        self.utxo_hashmap
+            .insert(*slip, (SlipSpentStatus::Unspent, block_id, transaction_type));
+    }
+
+    /// Return the `Block` id associated with a `Slip`'s current status
+    /// (the block it was spent in, or the block it was confirmed unspent
+    /// in), if the slip is present at all
+    ///
+    /// * `slip` - `&Slip` as key
+    pub fn slip_block_id(&self, slip: &Slip) -> Option<u64> {
+        self.utxo_hashmap.get(slip).map(|(_, block_id, _)| *block_id)
     }
 
-    /// Insert a `Slip`s byte array with the `Block` id
+    /// Return the `TransactionType` that produced `slip`, if it is
+    /// present in the UTXO set at all. Used by `Transaction::validate_against_utxoset`
+    /// to confirm a `StakerWithdrawal` actually references a prior `StakerDeposit`.
     ///
     /// * `slip` - `&Slip` as key
-    pub fn unspend_slip(&mut self, slip: &Slip) {
-        self.utxo_hashmap.insert(*slip, -1);
+    pub fn slip_transaction_type(&self, slip: &Slip) -> Option<TransactionType> {
+        self.utxo_hashmap
+            .get(slip)
+            .map(|(_, _, transaction_type)| *transaction_type)
     }
 
-    /// Return the `Block` id based on `Slip`
+    /// Return whether the `Slip` is present in the UTXO set at all,
+    /// regardless of its spend status
     ///
     /// * `slip` - `&Slip` as key
-    pub fn slip_block_id(&self, slip: &Slip) -> Option<&i64> {
-        self.utxo_hashmap.get(slip)
+    pub fn contains(&self, slip: &Slip) -> bool {
+        self.utxo_hashmap.contains_key(slip)
+    }
+
+    /// Return whether the `Slip` can be spent: it must be present in
+    /// the UTXO set, confirmed (not merely `Unconfirmed`), and not
+    /// already spent.
+    ///
+    /// * `slip` - `&Slip` to check
+    /// * `current_block_id` - reserved for future maturity-window rules
+    ///   (e.g. slips that aren't spendable until some number of blocks
+    ///   have passed)
+    pub fn is_spendable(&self, slip: &Slip, current_block_id: u64) -> bool {
+        let _ = current_block_id;
+        matches!(
+            self.utxo_hashmap.get(slip),
+            Some((SlipSpentStatus::Unspent, _, _))
+        )
+    }
+
+    /// Iterate over every still-unspent slip in the UTXO set along with
+    /// the `Block` id it was confirmed unspent in. Used by `crate::atr`
+    /// to find slips old enough to need rebroadcasting.
+    pub fn unspent_slips(&self) -> impl Iterator<Item = (&Slip, u64)> {
+        self.utxo_hashmap
+            .iter()
+            .filter_map(|(slip, (status, block_id, _))| match status {
+                SlipSpentStatus::Unspent => Some((slip, *block_id)),
+                _ => None,
+            })
     }
 }
 
@@ -87,7 +192,7 @@ mod test {
     use crate::{
         keypair::Keypair,
         slip::{Slip, SlipBroadcastType},
-        transaction::{Transaction, TransactionBroadcastType},
+        transaction::{Transaction, TransactionBroadcastType, TransactionType},
     };
     use std::collections::HashMap;
 
@@ -102,10 +207,22 @@ mod test {
         let mut shashmap = Shashmap::new();
         let keypair = Keypair::new();
         let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 0);
-        shashmap.insert(slip, 0);
+        shashmap.insert(slip, 0, TransactionType::Normal);
         assert!(shashmap.utxo_hashmap.contains_key(&slip));
     }
 
+    #[test]
+    fn shashmap_insert_is_spendable_test() {
+        // `insert` is the primary path by which a confirmed UTXO slip
+        // enters the set; it must be spendable immediately, not just
+        // present, or `is_spendable` is useless to consensus code.
+        let mut shashmap = Shashmap::new();
+        let keypair = Keypair::new();
+        let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 0);
+        shashmap.insert(slip, 0, TransactionType::Normal);
+        assert!(shashmap.is_spendable(&slip, 0));
+    }
+
     #[test]
     fn shashmap_insert_new_transaction_test() {
         let mut shashmap = Shashmap::new();
@@ -119,7 +236,11 @@ mod test {
         shashmap.insert_new_transaction(&tx);
 
         assert!(shashmap.utxo_hashmap.contains_key(&output_slip));
-        assert_eq!(shashmap.utxo_hashmap.get(&output_slip).unwrap(), &-1);
+        assert_eq!(
+            shashmap.utxo_hashmap.get(&output_slip).unwrap(),
+            &(SlipSpentStatus::Unconfirmed, 0, TransactionType::Normal)
+        );
+        assert!(!shashmap.is_spendable(&output_slip, 0));
     }
 
     #[test]
@@ -135,7 +256,11 @@ mod test {
         shashmap.spend_transaction(&tx, 0);
 
         assert!(shashmap.utxo_hashmap.contains_key(&input_slip));
-        assert_eq!(shashmap.utxo_hashmap.get(&input_slip).unwrap(), &0);
+        assert_eq!(
+            shashmap.utxo_hashmap.get(&input_slip).unwrap(),
+            &(SlipSpentStatus::Spent, 0, TransactionType::Normal)
+        );
+        assert!(!shashmap.is_spendable(&input_slip, 0));
     }
 
     #[test]
@@ -148,10 +273,14 @@ mod test {
 
         tx.add_input(input_slip);
 
-        shashmap.unspend_transaction(&tx);
+        shashmap.unspend_transaction(&tx, 0);
 
         assert!(shashmap.utxo_hashmap.contains_key(&input_slip));
-        assert_eq!(shashmap.utxo_hashmap.get(&input_slip).unwrap(), &-1);
+        assert_eq!(
+            shashmap.utxo_hashmap.get(&input_slip).unwrap(),
+            &(SlipSpentStatus::Unspent, 0, TransactionType::Normal)
+        );
+        assert!(shashmap.is_spendable(&input_slip, 0));
     }
 
     #[test]
@@ -164,7 +293,10 @@ mod test {
         shashmap.spend_slip(&input_slip, 0);
 
         assert!(shashmap.utxo_hashmap.contains_key(&input_slip));
-        assert_eq!(shashmap.utxo_hashmap.get(&input_slip).unwrap(), &0);
+        assert_eq!(
+            shashmap.utxo_hashmap.get(&input_slip).unwrap(),
+            &(SlipSpentStatus::Spent, 0, TransactionType::Normal)
+        );
     }
 
     #[test]
@@ -174,10 +306,13 @@ mod test {
         let keypair = Keypair::new();
         let input_slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 0);
 
-        shashmap.unspend_slip(&input_slip);
+        shashmap.unspend_slip(&input_slip, 0);
 
         assert!(shashmap.utxo_hashmap.contains_key(&input_slip));
-        assert_eq!(shashmap.utxo_hashmap.get(&input_slip).unwrap(), &-1);
+        assert_eq!(
+            shashmap.utxo_hashmap.get(&input_slip).unwrap(),
+            &(SlipSpentStatus::Unspent, 0, TransactionType::Normal)
+        );
     }
 
     #[test]
@@ -186,11 +321,50 @@ mod test {
 
         let keypair = Keypair::new();
         let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 0);
-        shashmap.insert(slip, 1);
+        shashmap.insert(slip, 1, TransactionType::Normal);
 
-        match shashmap.slip_block_id(&slip) {
-            Some(id) => assert_eq!(id, &1),
-            _ => assert!(false),
-        }
+        assert_eq!(shashmap.slip_block_id(&slip), Some(1));
+    }
+
+    #[test]
+    fn shashmap_slip_transaction_type_test() {
+        let mut shashmap = Shashmap::new();
+
+        let keypair = Keypair::new();
+        let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 0);
+
+        assert_eq!(shashmap.slip_transaction_type(&slip), None);
+        shashmap.insert(slip, 1, TransactionType::StakerDeposit);
+        assert_eq!(
+            shashmap.slip_transaction_type(&slip),
+            Some(TransactionType::StakerDeposit)
+        );
+    }
+
+    #[test]
+    fn shashmap_contains_test() {
+        let mut shashmap = Shashmap::new();
+
+        let keypair = Keypair::new();
+        let slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 0);
+
+        assert!(!shashmap.contains(&slip));
+        shashmap.insert(slip, 1, TransactionType::Normal);
+        assert!(shashmap.contains(&slip));
+    }
+
+    #[test]
+    fn shashmap_unspent_slips_test() {
+        let mut shashmap = Shashmap::new();
+
+        let keypair = Keypair::new();
+        let unspent_slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 0);
+        let spent_slip = Slip::new(keypair.public_key().clone(), SlipBroadcastType::Normal, 1);
+
+        shashmap.insert(unspent_slip, 5, TransactionType::Normal);
+        shashmap.spend_slip(&spent_slip, 5);
+
+        let unspent: Vec<(&Slip, u64)> = shashmap.unspent_slips().collect();
+        assert_eq!(unspent, vec![(&unspent_slip, 5)]);
     }
 }